@@ -1,13 +1,24 @@
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
 use ethers::prelude::*;
 use ethers::contract::EthEvent;
 use ethers::types::{Filter, H256};
 use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
 use sqlx::postgres::PgPoolOptions;
+use std::collections::HashMap;
 use std::sync::Arc;
 use eyre::Result;
 use tracing::{info, warn, error, debug};
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
+/// How many blocks of indexed history we're willing to walk back while
+/// searching for a common ancestor during reorg recovery.
+const MAX_REORG_DEPTH: u64 = 256;
+
 // Keep the existing UserOperationEvent struct
 
 fn init_tracing() {
@@ -56,51 +67,153 @@ struct UserOperationEvent {
     pub actual_gas_used: U256,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize tracing first
-    init_tracing();
+/// EntryPoint contract generations a network's config can select. The
+/// lifecycle event signatures are identical across all of them, so this
+/// doesn't currently drive any per-version topic0 or decoder selection — it
+/// exists purely so `networks.json` can record which generation a network is
+/// running, for operators' own bookkeeping.
+#[derive(Debug, Clone, Deserialize)]
+enum EntryPointVersion {
+    #[serde(rename = "v0.6")]
+    V06,
+    #[serde(rename = "v0.7")]
+    V07,
+    #[serde(rename = "v0.8")]
+    V08,
+}
 
-    // Load environment variables
-    dotenv::dotenv().ok();
+fn user_operation_event_topic0() -> H256 {
+    H256::from_slice(
+        &ethers::utils::keccak256("UserOperationEvent(bytes32,address,address,uint256,bool,uint256,uint256)")[..],
+    )
+}
 
-    // Get RPC URL
-    let rpc_url = std::env::var("RPC_URL")
-        .map_err(|e| {
-            error!("Failed to get RPC_URL: {}", e);
-            e
-        })?;
+/// All lifecycle event topics to watch for, used to build the
+/// `get_logs`/`subscribe_logs` filter.
+fn lifecycle_event_topics() -> Vec<H256> {
+    vec![
+        user_operation_event_topic0(),
+        account_deployed_topic0(),
+        user_operation_revert_reason_topic0(),
+        deposited_topic0(),
+        withdrawn_topic0(),
+    ]
+}
 
-    // Connect to provider
-    let provider: Provider<Ws> = Provider::connect(rpc_url).await
-        .map_err(|e| {
-            error!("Failed to connect to provider: {}", e);
-            e
-        })?;
-    let provider = Arc::new(provider);
+#[derive(Debug, Clone, EthEvent)]
+#[ethevent(
+    name = "AccountDeployed",
+    abi = "AccountDeployed(bytes32 userOpHash, address sender, address factory, address paymaster)"
+)]
+struct AccountDeployedEvent {
+    pub user_op_hash: H256,
+    pub sender: Address,
+    pub factory: Address,
+    pub paymaster: Address,
+}
+
+#[derive(Debug, Clone, EthEvent)]
+#[ethevent(
+    name = "UserOperationRevertReason",
+    abi = "UserOperationRevertReason(bytes32 userOpHash, address sender, uint256 nonce, bytes revertReason)"
+)]
+struct UserOperationRevertReasonEvent {
+    pub user_op_hash: H256,
+    pub sender: Address,
+    pub nonce: U256,
+    pub revert_reason: Bytes,
+}
+
+#[derive(Debug, Clone, EthEvent)]
+#[ethevent(
+    name = "Deposited",
+    abi = "Deposited(address account, uint256 totalDeposit)"
+)]
+struct DepositedEvent {
+    pub account: Address,
+    pub total_deposit: U256,
+}
+
+#[derive(Debug, Clone, EthEvent)]
+#[ethevent(
+    name = "Withdrawn",
+    abi = "Withdrawn(address account, address withdrawAddress, uint256 amount)"
+)]
+struct WithdrawnEvent {
+    pub account: Address,
+    pub withdraw_address: Address,
+    pub amount: U256,
+}
+
+fn account_deployed_topic0() -> H256 {
+    H256::from_slice(&ethers::utils::keccak256("AccountDeployed(bytes32,address,address,address)")[..])
+}
+
+fn user_operation_revert_reason_topic0() -> H256 {
+    H256::from_slice(&ethers::utils::keccak256("UserOperationRevertReason(bytes32,address,uint256,bytes)")[..])
+}
+
+fn deposited_topic0() -> H256 {
+    H256::from_slice(&ethers::utils::keccak256("Deposited(address,uint256)")[..])
+}
+
+fn withdrawn_topic0() -> H256 {
+    H256::from_slice(&ethers::utils::keccak256("Withdrawn(address,address,uint256)")[..])
+}
+
+/// One network to index, as described in the `networks` array of the config
+/// file pointed to by `CONFIG_PATH`.
+#[derive(Debug, Clone, Deserialize)]
+struct NetworkConfig {
+    name: String,
+    rpc_url: String,
+    entry_point_address: Address,
+    start_block: u64,
+    entry_point_version: EntryPointVersion,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct IndexerConfig {
+    networks: Vec<NetworkConfig>,
+}
 
-    // Verify the Connection to Ethereum
-    let block_number = provider.get_block_number().await
+/// Loads the multi-network config file. Replaces the old single-network
+/// `RPC_URL`/`START_BLOCK` env vars so one binary can index several chains
+/// and EntryPoint versions at once.
+fn load_config(path: &str) -> Result<IndexerConfig> {
+    let contents = std::fs::read_to_string(path)
         .map_err(|e| {
-            error!("Failed to get block number: {}", e);
+            error!("Failed to read config file {}: {}", path, e);
             e
         })?;
-    info!("Connected to Ethereum. Latest block: {}", block_number);
 
-    // Entry point address
-    let entry_point_address: Address = "0x0000000071727De22E5E9d8BAf0edAc6f37da032".parse()
+    let config: IndexerConfig = serde_json::from_str(&contents)
         .map_err(|e| {
-            error!("Failed to parse entry point address: {}", e);
+            error!("Failed to parse config file {}: {}", path, e);
             e
         })?;
 
-    // Compute topic0 for verification
-    let computed_topic0 = H256::from_slice(
-        &ethers::utils::keccak256("UserOperationEvent(bytes32,address,address,uint256,bool,uint256,uint256)")[..],
-    );
-    debug!("Computed topic0: {:?}", computed_topic0);
+    Ok(config)
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Initialize tracing first
+    init_tracing();
 
-    // Database connection
+    // Load environment variables
+    dotenv::dotenv().ok();
+
+    // Load the network config file
+    let config_path = std::env::var("CONFIG_PATH").unwrap_or_else(|_| "networks.json".to_string());
+    let config = load_config(&config_path)?;
+
+    if config.networks.is_empty() {
+        error!("No networks configured in {}", config_path);
+        return Err(eyre::eyre!("No networks configured in {}", config_path));
+    }
+
+    // Database connection, shared by every network's indexing task
     let db_url = std::env::var("DATABASE_URL")
         .map_err(|e| {
             error!("Failed to get DATABASE_URL: {}", e);
@@ -125,143 +238,354 @@ async fn main() -> Result<()> {
         })?;
     info!("Database connection test successful: {:?}", result.value);
 
-    // Get start block
-    let start_block: u64 = std::env::var("START_BLOCK")
-        .map_err(|e| {
-            error!("Failed to get START_BLOCK: {}", e);
-            e
-        })?
-        .parse()
-        .map_err(|e| {
-            error!("Failed to parse START_BLOCK: {}", e);
-            e
-        })?;
+    // Connect to every network's RPC endpoint up front so the indexing tasks
+    // and the read-side API below can share the same provider connections.
+    let mut providers = HashMap::with_capacity(config.networks.len());
+    for network in &config.networks {
+        info!("[{}] Connecting to {}", network.name, network.rpc_url);
 
-    // Index events
-    index_events(provider, db_pool, entry_point_address, start_block).await
-        .map_err(|e| {
-            error!("Event indexing failed: {}", e);
+        let provider: Provider<Ws> = Provider::connect(network.rpc_url.clone()).await
+            .map_err(|e| {
+                error!("[{}] Failed to connect to provider: {}", network.name, e);
+                e
+            })?;
+        let provider = Arc::new(provider);
+
+        let block_number = provider.get_block_number().await
+            .map_err(|e| {
+                error!("[{}] Failed to get block number: {}", network.name, e);
+                e
+            })?;
+        info!("[{}] Connected to chain. Latest block: {}", network.name, block_number);
+
+        providers.insert(network.name.clone(), provider);
+    }
+    let providers = Arc::new(providers);
+
+    info!("Starting indexers for {} network(s)", config.networks.len());
+
+    // Each network gets its own indexing task, so a stall or RPC error on one
+    // chain doesn't block the others.
+    let mut tasks = Vec::with_capacity(config.networks.len() + 1);
+    for network in config.networks {
+        let db_pool = db_pool.clone();
+        let provider = providers
+            .get(&network.name)
+            .expect("provider connected during startup")
+            .clone();
+        tasks.push(tokio::spawn(async move {
+            let name = network.name.clone();
+            if let Err(e) = run_network(network, db_pool, provider).await {
+                error!("Indexing failed for network {}: {}", name, e);
+            }
+        }));
+    }
+
+    let api_state = ApiState { db_pool: db_pool.clone(), providers };
+    tasks.push(tokio::spawn(async move {
+        if let Err(e) = serve_api(api_state).await {
+            error!("API server failed: {}", e);
+        }
+    }));
+
+    for task in tasks {
+        task.await.map_err(|e| {
+            error!("Indexing task panicked: {}", e);
             e
         })?;
+    }
 
     Ok(())
 }
 
+/// Runs `network`'s indexing loop against its already-connected provider.
+async fn run_network(network: NetworkConfig, db_pool: sqlx::PgPool, provider: Arc<Provider<Ws>>) -> Result<()> {
+    index_events(provider, db_pool, network).await
+}
+
 async fn index_events(
     provider: Arc<Provider<Ws>>,
     db_pool: sqlx::PgPool,
-    entry_point_address: Address,
-    from_block_number: u64,
+    network: NetworkConfig,
 ) -> Result<()> {
     // Fetch latest block number
     let latest_block_number = provider.get_block_number().await?.as_u64();
 
+    // Resume from the persisted checkpoint when we have one; start_block only
+    // acts as a floor so a fresh database still starts at the configured block.
+    let from_block_number = match get_checkpoint(&db_pool, &network.name).await? {
+        Some(last_indexed_block) => {
+            let resume_from = std::cmp::max(last_indexed_block + 1, network.start_block);
+            info!(
+                "[{}] Resuming from checkpoint: last indexed block {}, resuming at {}",
+                network.name, last_indexed_block, resume_from
+            );
+            resume_from
+        }
+        None => network.start_block,
+    };
+
     info!(
-        "Fetching historical events from block {} to {}",
-        from_block_number, latest_block_number
+        "[{}] Fetching historical events from block {} to {}",
+        network.name, from_block_number, latest_block_number
     );
 
-    // Fetch historical logs
-    let historical_logs = provider
-        .get_logs(&Filter::new()
-            .address(entry_point_address)
-            .topic0(H256::from_slice(
-                &ethers::utils::keccak256("UserOperationEvent(bytes32,address,address,uint256,bool,uint256,uint256)")[..],
-            ))
-            .from_block(BlockNumber::Number(from_block_number.into()))
-            .to_block(BlockNumber::Number(latest_block_number.into())))
-        .await
-        .map_err(|e| {
-            error!("Failed to fetch historical logs: {}", e);
-            e
-        })?;
-
-    // Process historical logs
-    for log in historical_logs {
-        match decode_user_operation_event(&log) {
-            Ok((event, block_number)) => {
-                debug!("Historical event: {:?}", event);
-                save_event_to_db(&db_pool, event, block_number).await
-                    .map_err(|e| {
-                        warn!("Failed to save historical event: {}", e);
-                        e
-                    })?;
-            }
-            Err(e) => {
-                warn!("Error decoding historical event: {:?}", e);
-            }
-        }
-    }
+    backfill_historical_events(&provider, &db_pool, &network, from_block_number, latest_block_number).await?;
 
     // Listen for new events
-    info!("Listening for new events from block {}", latest_block_number + 1);
+    info!("[{}] Listening for new events from block {}", network.name, latest_block_number + 1);
 
     let filter = Filter::new()
-        .address(entry_point_address)
-        .topic0(H256::from_slice(
-            &ethers::utils::keccak256("UserOperationEvent(bytes32,address,address,uint256,bool,uint256,uint256)")[..],
-        ))
+        .address(network.entry_point_address)
+        .topic0(lifecycle_event_topics())
         .from_block(BlockNumber::Number((latest_block_number + 1).into()));
 
-    let mut stream = provider.subscribe_logs(&filter)
+    let mut log_stream = provider.subscribe_logs(&filter)
+        .await
+        .map_err(|e| {
+            error!("[{}] Failed to subscribe to logs: {}", network.name, e);
+            e
+        })?;
+
+    // Drives `latest_seen_block` from the actual chain head rather than from
+    // event arrival, so a quiet period with no new EntryPoint logs still
+    // advances confirmations and flushes whatever is buffered.
+    let mut block_stream = provider.subscribe_blocks()
         .await
         .map_err(|e| {
-            error!("Failed to subscribe to logs: {}", e);
+            error!("[{}] Failed to subscribe to new blocks: {}", network.name, e);
             e
         })?;
 
-    while let Some(log) = stream.next().await {
-        match decode_user_operation_event(&log) {
-            Ok((event, block_number)) => {
-                debug!("New event: {:?}", event);
-                save_event_to_db(&db_pool, event, block_number).await
-                    .map_err(|e| {
-                        warn!("Failed to save new event: {}", e);
-                        e
-                    })?;
+    let confirmations = confirmation_depth();
+    info!(
+        "[{}] Buffering live events {} block(s) behind head before committing",
+        network.name, confirmations
+    );
+
+    // Logs at the chain head are where reorgs are most frequent, so we hold
+    // them in memory per block until they're `confirmations` blocks deep,
+    // rather than committing straight to Postgres as they arrive.
+    let mut pending_blocks: std::collections::BTreeMap<u64, Vec<(Log, H256)>> = std::collections::BTreeMap::new();
+    let mut latest_seen_block = latest_block_number;
+
+    loop {
+        tokio::select! {
+            maybe_log = log_stream.next() => {
+                let Some(log) = maybe_log else { break; };
+                let (block_number, block_hash) = match log_block_info(&log) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        warn!("[{}] Error reading block info from new log: {:?}", network.name, e);
+                        continue;
+                    }
+                };
+                pending_blocks.entry(block_number).or_default().push((log, block_hash));
             }
-            Err(e) => {
-                warn!("Error decoding new event: {:?}", e);
+            maybe_block = block_stream.next() => {
+                let Some(block) = maybe_block else { break; };
+                if let Some(number) = block.number {
+                    latest_seen_block = latest_seen_block.max(number.as_u64());
+                }
             }
         }
+
+        let confirmed_up_to = latest_seen_block.saturating_sub(confirmations);
+        let ready_blocks: Vec<u64> = pending_blocks.range(..=confirmed_up_to).map(|(b, _)| *b).collect();
+
+        for ready_block in ready_blocks {
+            let logs = pending_blocks.remove(&ready_block).expect("key came from this map");
+            flush_confirmed_block(&provider, &db_pool, &network, ready_block, logs).await?;
+        }
+
+        // Advance the checkpoint to the confirmed head every cycle, even when
+        // no block in range carried an EntryPoint event, so `sync_status`
+        // doesn't report the indexer falling behind during quiet periods.
+        // `update_checkpoint` never moves the value backwards, so this is
+        // safe to call unconditionally.
+        update_checkpoint(&db_pool, &network.name, confirmed_up_to).await?;
     }
 
     Ok(())
 }
 
-fn decode_user_operation_event(log: &Log) -> Result<(UserOperationEvent, u64), ethers::abi::Error> {
-    // Ensure the log contains the expected topics
-    if log.topics.len() != 4 {
+/// Depth, in blocks, a live event must sit behind the chain head before it's
+/// committed. Overridable via `CONFIRMATIONS`.
+const DEFAULT_CONFIRMATIONS: u64 = 5;
+
+fn confirmation_depth() -> u64 {
+    std::env::var("CONFIRMATIONS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_CONFIRMATIONS)
+}
+
+/// Commits a block's buffered logs once it has reached the confirmation
+/// depth, unless the chain has since reorged it away, in which case the
+/// buffered logs are dropped as phantom data instead of being persisted.
+async fn flush_confirmed_block(
+    provider: &Provider<Ws>,
+    db_pool: &sqlx::PgPool,
+    network: &NetworkConfig,
+    block_number: u64,
+    logs: Vec<(Log, H256)>,
+) -> Result<()> {
+    let mut cache = BlockCache::new();
+
+    let buffered_hash = logs[0].1;
+    let canonical_hash = fetch_block_hash_cached(provider, &mut cache, block_number).await?;
+
+    if buffered_hash != canonical_hash {
         warn!(
-            "Invalid number of topics: expected 4, got {}",
-            log.topics.len()
+            "[{}] Buffered hash for block {} ({:?}) is no longer canonical (now {:?}); re-indexing from the winning fork",
+            network.name, block_number, buffered_hash, canonical_hash
         );
-        return Err(ethers::abi::Error::InvalidData);
+        // The buffered logs belong to a block that's no longer part of the
+        // canonical chain. Just dropping them would permanently lose
+        // whatever the winning fork emitted at this height, so re-fetch and
+        // index the replacement logs the same way a mid-chain reorg does.
+        return reindex_range(provider, db_pool, network, block_number, block_number).await;
     }
 
-    // Decode indexed fields from topics
-    let user_op_hash = H256::from(log.topics[1]); // topic[1]: bytes32
-    let sender: Address = log.topics[2].into();  // topic[2]: address
-    let paymaster: Address = log.topics[3].into(); // topic[3]: address
+    // The caller advances the checkpoint to the confirmed head once per
+    // cycle, covering every block it flushes here, so we don't need to do it
+    // per log.
+    for (log, _) in logs {
+        process_log(provider, db_pool, network, &mut cache, &log).await?;
+    }
 
-    // Decode non-indexed fields from data
-    let data = &log.data.0;
+    Ok(())
+}
 
-    if data.len() != 128 {
-        warn!(
-            "Unexpected data length: expected 128, got {}",
-            data.len()
-        );
-        return Err(ethers::abi::Error::InvalidData);
+/// Reads the last block we durably committed for `network`, if the indexer
+/// has run before.
+async fn get_checkpoint(db_pool: &sqlx::PgPool, network: &str) -> Result<Option<u64>> {
+    let row = sqlx::query!(
+        "SELECT last_indexed_block FROM checkpoint WHERE network = $1",
+        network
+    )
+    .fetch_optional(db_pool)
+    .await?;
+
+    Ok(row.map(|r| r.last_indexed_block as u64))
+}
+
+/// Advances the persisted checkpoint for `network` to `block_number`, never
+/// moving it backwards.
+async fn update_checkpoint(db_pool: &sqlx::PgPool, network: &str, block_number: u64) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO checkpoint (network, last_indexed_block)
+        VALUES ($1, $2)
+        ON CONFLICT (network) DO UPDATE
+        SET last_indexed_block = GREATEST(checkpoint.last_indexed_block, excluded.last_indexed_block)
+        "#,
+        network,
+        block_number as i64
+    )
+    .execute(db_pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Default width, in blocks, of each historical `get_logs` window. Overridable
+/// via `BACKFILL_WINDOW_BLOCKS` since the right size depends on the RPC provider.
+const DEFAULT_BACKFILL_WINDOW_BLOCKS: u64 = 2000;
+const MIN_BACKFILL_WINDOW_BLOCKS: u64 = 50;
+const MAX_BACKFILL_WINDOW_BLOCKS: u64 = 10_000;
+/// Consecutive successful windows required before we double the window size back up.
+const WINDOW_GROWTH_STREAK: u32 = 3;
+
+/// Backfills `[from_block, to_block]` in fixed-size windows instead of one
+/// unbounded `get_logs` call, since providers reject or time out on overly
+/// large ranges. The window shrinks on a "too many results"-shaped error and
+/// grows back after a streak of successes. Each window's logs are decoded and
+/// committed, and the checkpoint advanced, before moving on to the next one.
+async fn backfill_historical_events(
+    provider: &Provider<Ws>,
+    db_pool: &sqlx::PgPool,
+    network: &NetworkConfig,
+    from_block: u64,
+    to_block: u64,
+) -> Result<()> {
+    let mut window = std::env::var("BACKFILL_WINDOW_BLOCKS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_BACKFILL_WINDOW_BLOCKS);
+
+    let mut current_block = from_block;
+    let mut success_streak: u32 = 0;
+
+    while current_block <= to_block {
+        let window_end = (current_block + window - 1).min(to_block);
+
+        let logs_result = provider
+            .get_logs(&Filter::new()
+                .address(network.entry_point_address)
+                .topic0(lifecycle_event_topics())
+                .from_block(BlockNumber::Number(current_block.into()))
+                .to_block(BlockNumber::Number(window_end.into())))
+            .await;
+
+        match logs_result {
+            Ok(logs) => {
+                debug!(
+                    "[{}] Fetched {} historical logs for window {}..={}",
+                    network.name, logs.len(), current_block, window_end
+                );
+
+                // Scoped to this window so the cache can't grow unbounded
+                // across a long backfill while still deduplicating per-block
+                // work within it.
+                let mut cache = BlockCache::new();
+                for log in logs {
+                    process_log(provider, db_pool, network, &mut cache, &log).await?;
+                }
+
+                update_checkpoint(db_pool, &network.name, window_end).await?;
+
+                current_block = window_end + 1;
+                success_streak += 1;
+                if success_streak >= WINDOW_GROWTH_STREAK && window < MAX_BACKFILL_WINDOW_BLOCKS {
+                    window = (window * 2).min(MAX_BACKFILL_WINDOW_BLOCKS);
+                    success_streak = 0;
+                    debug!("[{}] Growing backfill window to {} blocks", network.name, window);
+                }
+            }
+            Err(e) if is_too_many_results_error(&e) && window > MIN_BACKFILL_WINDOW_BLOCKS => {
+                window = (window / 2).max(MIN_BACKFILL_WINDOW_BLOCKS);
+                success_streak = 0;
+                warn!(
+                    "[{}] Provider rejected window {}..={} ({}); shrinking window to {} blocks and retrying",
+                    network.name, current_block, window_end, e, window
+                );
+            }
+            Err(e) => {
+                error!("[{}] Failed to fetch historical logs for window {}..={}: {}", network.name, current_block, window_end, e);
+                return Err(e.into());
+            }
+        }
     }
 
-    // Decode fields accounting for padding
-    let nonce = U256::from_big_endian(&data[0..32]);          // uint256
-    let success = data[63] != 0;                              // bool (1 byte after 31 bytes of padding)
-    let actual_gas_cost = U256::from_big_endian(&data[64..96]); // uint256
-    let actual_gas_used = U256::from_big_endian(&data[96..128]); // uint256
+    Ok(())
+}
+
+/// Providers surface "range too large" in the error message rather than as a
+/// distinct error variant, so we match on the common phrasings.
+fn is_too_many_results_error(error: &ProviderError) -> bool {
+    let message = error.to_string().to_lowercase();
+    message.contains("query returned more than")
+        || message.contains("more than 10000 results")
+        || message.contains("response size exceeded")
+        || message.contains("block range")
+        || message.contains("timeout")
+        || message.contains("too many")
+}
 
-    // Get the block number from the log
+/// Extracts the block number and hash that produced `log`, independent of
+/// which EntryPoint event it carries.
+fn log_block_info(log: &Log) -> Result<(u64, H256), ethers::abi::Error> {
     let block_number = log
         .block_number
         .ok_or(ethers::abi::Error::InvalidData)
@@ -271,42 +595,473 @@ fn decode_user_operation_event(log: &Log) -> Result<(UserOperationEvent, u64), e
         })?
         .as_u64();
 
-    debug!(
-        "Decoded UserOperationEvent: hash={:?}, sender={:?}, paymaster={:?}, nonce={}, success={}, gas_cost={}, gas_used={}, block={}",
-        user_op_hash, sender, paymaster, nonce, success, actual_gas_cost, actual_gas_used, block_number
-    );
+    let block_hash = log
+        .block_hash
+        .ok_or(ethers::abi::Error::InvalidData)
+        .map_err(|e| {
+            warn!("Failed to extract block hash from log");
+            e
+        })?;
 
-    // Construct the UserOperationEvent struct
-    Ok((
-        UserOperationEvent {
-            user_op_hash,
-            sender,
-            paymaster,
-            nonce,
-            success,
-            actual_gas_cost,
-            actual_gas_used,
-        },
-        block_number,
-    ))
+    Ok((block_number, block_hash))
 }
 
-async fn save_event_to_db(
+/// Caches canonical block hashes fetched from the provider, and remembers
+/// which block `handle_block` has already run its reorg check for, so a
+/// batch of logs belonging to the same block only costs one `get_block` and
+/// one reorg check instead of one per log.
+struct BlockCache {
+    hashes: HashMap<u64, H256>,
+    last_handled_block: Option<u64>,
+}
+
+impl BlockCache {
+    fn new() -> Self {
+        Self { hashes: HashMap::new(), last_handled_block: None }
+    }
+}
+
+/// Fetches `block_number`'s canonical hash, reusing `cache` if we've already
+/// looked it up in this batch.
+async fn fetch_block_hash_cached(provider: &Provider<Ws>, cache: &mut BlockCache, block_number: u64) -> Result<H256> {
+    if let Some(&hash) = cache.hashes.get(&block_number) {
+        return Ok(hash);
+    }
+
+    let hash = fetch_block_hash(provider, block_number).await?;
+    cache.hashes.insert(block_number, hash);
+    Ok(hash)
+}
+
+/// Runs reorg bookkeeping for `log`'s block, then decodes and persists it
+/// through the right table for its event type. Returns the log's block
+/// number, or `None` if the log carries no block info and was skipped.
+async fn process_log(
+    provider: &Provider<Ws>,
     db_pool: &sqlx::PgPool,
-    event: UserOperationEvent,
+    network: &NetworkConfig,
+    cache: &mut BlockCache,
+    log: &Log,
+) -> Result<Option<u64>> {
+    let (block_number, block_hash) = match log_block_info(log) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("[{}] Error reading block info from log: {:?}", network.name, e);
+            return Ok(None);
+        }
+    };
+
+    handle_block(provider, db_pool, network, cache, block_number, block_hash).await?;
+    dispatch_entry_point_log(db_pool, &network.name, log).await?;
+
+    Ok(Some(block_number))
+}
+
+/// Matches `log`'s topic0 against the known EntryPoint lifecycle events and
+/// decodes+saves it through the matching table.
+async fn dispatch_entry_point_log(db_pool: &sqlx::PgPool, network: &str, log: &Log) -> Result<()> {
+    let Some(&topic0) = log.topics.first() else {
+        warn!("[{}] Log has no topics, skipping", network);
+        return Ok(());
+    };
+
+    if topic0 == account_deployed_topic0() {
+        match decode_account_deployed_event(log) {
+            Ok(event) => save_account_deployed_event(db_pool, network, event, log).await,
+            Err(e) => {
+                warn!("[{}] Error decoding AccountDeployed event: {:?}", network, e);
+                Ok(())
+            }
+        }
+    } else if topic0 == user_operation_revert_reason_topic0() {
+        match decode_user_operation_revert_reason_event(log) {
+            Ok(event) => save_user_operation_revert_reason_event(db_pool, network, event, log).await,
+            Err(e) => {
+                warn!("[{}] Error decoding UserOperationRevertReason event: {:?}", network, e);
+                Ok(())
+            }
+        }
+    } else if topic0 == deposited_topic0() {
+        match decode_deposited_event(log) {
+            Ok(event) => save_deposited_event(db_pool, network, event, log).await,
+            Err(e) => {
+                warn!("[{}] Error decoding Deposited event: {:?}", network, e);
+                Ok(())
+            }
+        }
+    } else if topic0 == withdrawn_topic0() {
+        match decode_withdrawn_event(log) {
+            Ok(event) => save_withdrawn_event(db_pool, network, event, log).await,
+            Err(e) => {
+                warn!("[{}] Error decoding Withdrawn event: {:?}", network, e);
+                Ok(())
+            }
+        }
+    } else {
+        // Anything else is assumed to be a UserOperationEvent for this
+        // network's EntryPoint version (already matched by the filter's topic0 list).
+        match decode_user_operation_event(log) {
+            Ok((event, block_number, _)) => save_event_to_db(db_pool, network, event, block_number).await,
+            Err(e) => {
+                warn!("[{}] Error decoding UserOperationEvent: {:?}", network, e);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Reconciles the chain of `indexed_blocks` with the provider's canonical
+/// chain before an event belonging to `block_number` is persisted.
+///
+/// `indexed_blocks` only has a row per block that actually carried an
+/// EntryPoint event, so it can have gaps; checking strictly against
+/// `block_number - 1` would silently skip the check whenever the
+/// immediately preceding block was empty. Instead we compare against the
+/// most recent block we *do* have a row for: if our stored hash for it no
+/// longer matches the provider's canonical hash, a reorg has occurred. We
+/// walk backwards until we find a block where our stored hash still
+/// matches, roll back everything indexed after that ancestor, and
+/// re-fetch the canonical logs for the rolled-back range before continuing.
+async fn handle_block(
+    provider: &Provider<Ws>,
+    db_pool: &sqlx::PgPool,
+    network: &NetworkConfig,
+    cache: &mut BlockCache,
     block_number: u64,
+    block_hash: H256,
 ) -> Result<()> {
-    let user_op_hash = format!("{:?}", event.user_op_hash);
-    let sender = format!("{:?}", event.sender);
-    let paymaster = format!("{:?}", event.paymaster);
-    let nonce = format!("0x{:064x}", event.nonce); // Ensure consistent formatting
+    // Every log in the same block has already paid for this block's reorg
+    // check and `indexed_blocks` row on its first pass through.
+    if cache.last_handled_block == Some(block_number) {
+        return Ok(());
+    }
 
-    match sqlx::query!(
-        r#"
-        INSERT INTO user_operation_events (user_op_hash, sender, paymaster, nonce, success, actual_gas_cost, actual_gas_used, block_number)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-        ON CONFLICT (user_op_hash, nonce) DO NOTHING
+    if let Some(last_indexed) = get_last_indexed_block(db_pool, &network.name).await? {
+        if last_indexed < block_number {
+            if let Some(stored_hash) = get_stored_block_hash(db_pool, &network.name, last_indexed).await? {
+                let canonical_hash = fetch_block_hash_cached(provider, cache, last_indexed).await?;
+                if stored_hash != format!("{:?}", canonical_hash) {
+                    warn!(
+                        "[{}] Reorg detected: stored hash for block {} is {} but canonical is now {:?}",
+                        network.name, last_indexed, stored_hash, canonical_hash
+                    );
+                    let ancestor = find_common_ancestor(provider, db_pool, &network.name, last_indexed).await?;
+                    rollback_to_block(db_pool, &network.name, ancestor).await?;
+                    reindex_range(provider, db_pool, network, ancestor + 1, block_number - 1).await?;
+                }
+            }
+        }
+    }
+
+    record_indexed_block(db_pool, &network.name, block_number, block_hash).await?;
+    cache.last_handled_block = Some(block_number);
+
+    Ok(())
+}
+
+/// Returns the highest block number we have an `indexed_blocks` row for on
+/// `network`, if we've indexed anything yet.
+async fn get_last_indexed_block(db_pool: &sqlx::PgPool, network: &str) -> Result<Option<u64>> {
+    let row = sqlx::query!(
+        "SELECT MAX(block_number) as max_block FROM indexed_blocks WHERE network = $1",
+        network
+    )
+    .fetch_one(db_pool)
+    .await?;
+
+    Ok(row.max_block.map(|b| b as u64))
+}
+
+/// Whether the backward search in `find_common_ancestor` has exhausted its
+/// options for `candidate` — either it's walked back to genesis or it's
+/// walked back `MAX_REORG_DEPTH` blocks from where the search started.
+fn reorg_search_exhausted(candidate: u64, from_block: u64) -> bool {
+    candidate == 0 || from_block - candidate >= MAX_REORG_DEPTH
+}
+
+/// Walks backwards from `from_block` comparing our stored block hashes
+/// against the provider's canonical hashes until they agree, returning the
+/// block number of that common ancestor. If the search is exhausted first
+/// (see `reorg_search_exhausted`), the block it stopped at is returned.
+async fn find_common_ancestor(
+    provider: &Provider<Ws>,
+    db_pool: &sqlx::PgPool,
+    network: &str,
+    from_block: u64,
+) -> Result<u64> {
+    let mut candidate = from_block;
+
+    loop {
+        let canonical_hash = fetch_block_hash(provider, candidate).await?;
+        match get_stored_block_hash(db_pool, network, candidate).await? {
+            Some(stored_hash) if stored_hash == format!("{:?}", canonical_hash) => {
+                info!("[{}] Found common ancestor at block {}", network, candidate);
+                return Ok(candidate);
+            }
+            _ if reorg_search_exhausted(candidate, from_block) => {
+                warn!(
+                    "[{}] Reached reorg search limit at block {}; treating it as the common ancestor",
+                    network, candidate
+                );
+                return Ok(candidate);
+            }
+            _ => candidate -= 1,
+        }
+    }
+}
+
+/// Deletes all indexed events and block records after `ancestor_block` for
+/// `network` in a single transaction, so a rollback can never leave these
+/// tables inconsistent with each other.
+async fn rollback_to_block(db_pool: &sqlx::PgPool, network: &str, ancestor_block: u64) -> Result<()> {
+    warn!("[{}] Rolling back indexed data after block {}", network, ancestor_block);
+
+    let mut tx = db_pool.begin().await?;
+
+    sqlx::query!(
+        "DELETE FROM user_operation_events WHERE network = $1 AND block_number > $2",
+        network,
+        ancestor_block as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM account_deployed_events WHERE network = $1 AND block_number > $2",
+        network,
+        ancestor_block as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM user_operation_revert_reason_events WHERE network = $1 AND block_number > $2",
+        network,
+        ancestor_block as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM deposited_events WHERE network = $1 AND block_number > $2",
+        network,
+        ancestor_block as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM withdrawn_events WHERE network = $1 AND block_number > $2",
+        network,
+        ancestor_block as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM indexed_blocks WHERE network = $1 AND block_number > $2",
+        network,
+        ancestor_block as i64
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Re-fetches and re-applies canonical logs for a range that was just rolled
+/// back, so the rolled-back blocks end up re-indexed from the winning fork.
+async fn reindex_range(
+    provider: &Provider<Ws>,
+    db_pool: &sqlx::PgPool,
+    network: &NetworkConfig,
+    from_block: u64,
+    to_block: u64,
+) -> Result<()> {
+    if from_block > to_block {
+        return Ok(());
+    }
+
+    info!("[{}] Re-indexing blocks {}..={} after reorg", network.name, from_block, to_block);
+
+    let logs = provider
+        .get_logs(&Filter::new()
+            .address(network.entry_point_address)
+            .topic0(lifecycle_event_topics())
+            .from_block(BlockNumber::Number(from_block.into()))
+            .to_block(BlockNumber::Number(to_block.into())))
+        .await
+        .map_err(|e| {
+            error!("[{}] Failed to fetch logs while re-indexing after reorg: {}", network.name, e);
+            e
+        })?;
+
+    let mut last_handled_block = None;
+
+    for log in logs {
+        // `handle_block`'s own reorg check is skipped here since we're already
+        // inside a reorg recovery; we still need the block hash recorded, once
+        // per block rather than once per log.
+        if let Ok((block_number, block_hash)) = log_block_info(&log) {
+            if last_handled_block != Some(block_number) {
+                record_indexed_block(db_pool, &network.name, block_number, block_hash).await?;
+                last_handled_block = Some(block_number);
+            }
+        }
+        dispatch_entry_point_log(db_pool, &network.name, &log).await?;
+    }
+
+    Ok(())
+}
+
+/// Fetches the canonical hash of `block_number` from the provider.
+async fn fetch_block_hash(provider: &Provider<Ws>, block_number: u64) -> Result<H256> {
+    let block = provider
+        .get_block(block_number)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch block {}: {}", block_number, e);
+            e
+        })?
+        .ok_or_else(|| eyre::eyre!("Block {} not found on canonical chain", block_number))?;
+
+    block
+        .hash
+        .ok_or_else(|| eyre::eyre!("Block {} has no hash", block_number))
+}
+
+/// Looks up the hash we recorded for `block_number` on `network` in
+/// `indexed_blocks`, if any.
+async fn get_stored_block_hash(db_pool: &sqlx::PgPool, network: &str, block_number: u64) -> Result<Option<String>> {
+    let row = sqlx::query!(
+        "SELECT block_hash FROM indexed_blocks WHERE network = $1 AND block_number = $2",
+        network,
+        block_number as i64
+    )
+    .fetch_optional(db_pool)
+    .await?;
+
+    Ok(row.map(|r| r.block_hash))
+}
+
+/// Upserts the canonical block hash for `block_number` on `network`. We only
+/// ever compare stored hashes against the provider's canonical hash at the
+/// same height (see `handle_block`/`find_common_ancestor`), so there's no
+/// parent hash to maintain here.
+async fn record_indexed_block(
+    db_pool: &sqlx::PgPool,
+    network: &str,
+    block_number: u64,
+    block_hash: H256,
+) -> Result<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO indexed_blocks (network, block_number, block_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (network, block_number) DO UPDATE
+        SET block_hash = excluded.block_hash
+        "#,
+        network,
+        block_number as i64,
+        format!("{:?}", block_hash)
+    )
+    .execute(db_pool)
+    .await?;
+
+    Ok(())
+}
+
+fn decode_user_operation_event(log: &Log) -> Result<(UserOperationEvent, u64, H256), ethers::abi::Error> {
+    // Ensure the log contains the expected topics
+    if log.topics.len() != 4 {
+        warn!(
+            "Invalid number of topics: expected 4, got {}",
+            log.topics.len()
+        );
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    // Decode indexed fields from topics
+    let user_op_hash = H256::from(log.topics[1]); // topic[1]: bytes32
+    let sender: Address = log.topics[2].into();  // topic[2]: address
+    let paymaster: Address = log.topics[3].into(); // topic[3]: address
+
+    // Decode non-indexed fields from data
+    let data = &log.data.0;
+
+    if data.len() != 128 {
+        warn!(
+            "Unexpected data length: expected 128, got {}",
+            data.len()
+        );
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    // Decode fields accounting for padding
+    let nonce = U256::from_big_endian(&data[0..32]);          // uint256
+    let success = data[63] != 0;                              // bool (1 byte after 31 bytes of padding)
+    let actual_gas_cost = U256::from_big_endian(&data[64..96]); // uint256
+    let actual_gas_used = U256::from_big_endian(&data[96..128]); // uint256
+
+    // Get the block number and hash from the log
+    let block_number = log
+        .block_number
+        .ok_or(ethers::abi::Error::InvalidData)
+        .map_err(|e| {
+            warn!("Failed to extract block number from log");
+            e
+        })?
+        .as_u64();
+
+    let block_hash = log
+        .block_hash
+        .ok_or(ethers::abi::Error::InvalidData)
+        .map_err(|e| {
+            warn!("Failed to extract block hash from log");
+            e
+        })?;
+
+    debug!(
+        "Decoded UserOperationEvent: hash={:?}, sender={:?}, paymaster={:?}, nonce={}, success={}, gas_cost={}, gas_used={}, block={}",
+        user_op_hash, sender, paymaster, nonce, success, actual_gas_cost, actual_gas_used, block_number
+    );
+
+    // Construct the UserOperationEvent struct
+    Ok((
+        UserOperationEvent {
+            user_op_hash,
+            sender,
+            paymaster,
+            nonce,
+            success,
+            actual_gas_cost,
+            actual_gas_used,
+        },
+        block_number,
+        block_hash,
+    ))
+}
+
+async fn save_event_to_db(
+    db_pool: &sqlx::PgPool,
+    network: &str,
+    event: UserOperationEvent,
+    block_number: u64,
+) -> Result<()> {
+    let user_op_hash = format!("{:?}", event.user_op_hash);
+    let sender = format!("{:?}", event.sender);
+    let paymaster = format!("{:?}", event.paymaster);
+    let nonce = format!("0x{:064x}", event.nonce); // Ensure consistent formatting
+
+    match sqlx::query!(
+        r#"
+        INSERT INTO user_operation_events (network, user_op_hash, sender, paymaster, nonce, success, actual_gas_cost, actual_gas_used, block_number)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT (network, user_op_hash, nonce) DO NOTHING
         "#,
+        network,
         user_op_hash,
         sender,
         paymaster,
@@ -322,23 +1077,734 @@ async fn save_event_to_db(
         Ok(result) => {
             if result.rows_affected() > 0 {
                 info!(
-                    "Event saved to database at block {} (user_op_hash: {})",
-                    block_number, user_op_hash
+                    "[{}] Event saved to database at block {} (user_op_hash: {})",
+                    network, block_number, user_op_hash
                 );
             } else {
                 debug!(
-                    "Duplicate event skipped at block {} (user_op_hash: {})",
-                    block_number, user_op_hash
+                    "[{}] Duplicate event skipped at block {} (user_op_hash: {})",
+                    network, block_number, user_op_hash
                 );
             }
             Ok(())
         }
         Err(e) => {
             error!(
-                "Failed to save event to database (user_op_hash: {}): {}",
-                user_op_hash, e
+                "[{}] Failed to save event to database (user_op_hash: {}): {}",
+                network, user_op_hash, e
             );
             Err(e.into())
         }
     }
-}
\ No newline at end of file
+}
+
+/// Identifies a log uniquely enough to dedupe re-processing (e.g. a live log
+/// re-seen during a historical backfill, or a reorg re-indexing the same range).
+fn log_identity(log: &Log) -> Result<(String, i64), ethers::abi::Error> {
+    let tx_hash = log
+        .transaction_hash
+        .ok_or(ethers::abi::Error::InvalidData)
+        .map_err(|e| {
+            warn!("Failed to extract transaction hash from log");
+            e
+        })?;
+    let log_index = log
+        .log_index
+        .ok_or(ethers::abi::Error::InvalidData)
+        .map_err(|e| {
+            warn!("Failed to extract log index from log");
+            e
+        })?
+        .as_u64() as i64;
+
+    Ok((format!("{:?}", tx_hash), log_index))
+}
+
+fn decode_account_deployed_event(log: &Log) -> Result<AccountDeployedEvent, ethers::abi::Error> {
+    if log.topics.len() != 3 {
+        warn!("Invalid number of topics for AccountDeployed: expected 3, got {}", log.topics.len());
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    let data = &log.data.0;
+    if data.len() != 64 {
+        warn!("Unexpected data length for AccountDeployed: expected 64, got {}", data.len());
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    Ok(AccountDeployedEvent {
+        user_op_hash: H256::from(log.topics[1]),
+        sender: log.topics[2].into(),
+        factory: Address::from_slice(&data[12..32]),
+        paymaster: Address::from_slice(&data[44..64]),
+    })
+}
+
+async fn save_account_deployed_event(
+    db_pool: &sqlx::PgPool,
+    network: &str,
+    event: AccountDeployedEvent,
+    log: &Log,
+) -> Result<()> {
+    let (tx_hash, log_index) = log_identity(log)?;
+    let (block_number, _) = log_block_info(log)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO account_deployed_events (network, user_op_hash, sender, factory, paymaster, block_number, tx_hash, log_index)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (network, tx_hash, log_index) DO NOTHING
+        "#,
+        network,
+        format!("{:?}", event.user_op_hash),
+        format!("{:?}", event.sender),
+        format!("{:?}", event.factory),
+        format!("{:?}", event.paymaster),
+        block_number as i64,
+        tx_hash,
+        log_index
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        error!("[{}] Failed to save AccountDeployed event: {}", network, e);
+        e
+    })?;
+
+    Ok(())
+}
+
+/// `UserOperationRevertReason`'s non-indexed tail is `(uint256 nonce, bytes revertReason)`,
+/// so `revertReason` is ABI-encoded with the usual dynamic-type offset/length prefix.
+fn decode_user_operation_revert_reason_event(log: &Log) -> Result<UserOperationRevertReasonEvent, ethers::abi::Error> {
+    if log.topics.len() != 3 {
+        warn!("Invalid number of topics for UserOperationRevertReason: expected 3, got {}", log.topics.len());
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    let data = &log.data.0;
+    if data.len() < 64 {
+        warn!("Unexpected data length for UserOperationRevertReason: got {}", data.len());
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    let nonce = U256::from_big_endian(&data[0..32]);
+    // `offset`/`length` come straight off the wire as full uint256 words, so a
+    // malformed or adversarial log can encode a value that doesn't fit in a
+    // `usize` — bounds-check via `try_into` rather than `as_usize()`, which
+    // would panic the indexing task on truncation.
+    let offset: usize = U256::from_big_endian(&data[32..64])
+        .try_into()
+        .map_err(|_| {
+            warn!("UserOperationRevertReason bytes offset does not fit in usize");
+            ethers::abi::Error::InvalidData
+        })?;
+
+    let offset_end = offset.checked_add(32).ok_or(ethers::abi::Error::InvalidData)?;
+    if data.len() < offset_end {
+        warn!("UserOperationRevertReason data too short for its bytes offset");
+        return Err(ethers::abi::Error::InvalidData);
+    }
+    let length: usize = U256::from_big_endian(&data[offset..offset_end])
+        .try_into()
+        .map_err(|_| {
+            warn!("UserOperationRevertReason bytes length does not fit in usize");
+            ethers::abi::Error::InvalidData
+        })?;
+
+    let data_end = offset_end.checked_add(length).ok_or(ethers::abi::Error::InvalidData)?;
+    if data.len() < data_end {
+        warn!("UserOperationRevertReason data too short for its bytes length");
+        return Err(ethers::abi::Error::InvalidData);
+    }
+    let revert_reason = Bytes::from(data[offset_end..data_end].to_vec());
+
+    Ok(UserOperationRevertReasonEvent {
+        user_op_hash: H256::from(log.topics[1]),
+        sender: log.topics[2].into(),
+        nonce,
+        revert_reason,
+    })
+}
+
+async fn save_user_operation_revert_reason_event(
+    db_pool: &sqlx::PgPool,
+    network: &str,
+    event: UserOperationRevertReasonEvent,
+    log: &Log,
+) -> Result<()> {
+    let (tx_hash, log_index) = log_identity(log)?;
+    let (block_number, _) = log_block_info(log)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO user_operation_revert_reason_events (network, user_op_hash, sender, nonce, revert_reason, block_number, tx_hash, log_index)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        ON CONFLICT (network, tx_hash, log_index) DO NOTHING
+        "#,
+        network,
+        format!("{:?}", event.user_op_hash),
+        format!("{:?}", event.sender),
+        format!("0x{:064x}", event.nonce),
+        event.revert_reason.to_string(),
+        block_number as i64,
+        tx_hash,
+        log_index
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        error!("[{}] Failed to save UserOperationRevertReason event: {}", network, e);
+        e
+    })?;
+
+    Ok(())
+}
+
+fn decode_deposited_event(log: &Log) -> Result<DepositedEvent, ethers::abi::Error> {
+    if log.topics.len() != 2 {
+        warn!("Invalid number of topics for Deposited: expected 2, got {}", log.topics.len());
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    let data = &log.data.0;
+    if data.len() != 32 {
+        warn!("Unexpected data length for Deposited: expected 32, got {}", data.len());
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    Ok(DepositedEvent {
+        account: log.topics[1].into(),
+        total_deposit: U256::from_big_endian(&data[0..32]),
+    })
+}
+
+async fn save_deposited_event(
+    db_pool: &sqlx::PgPool,
+    network: &str,
+    event: DepositedEvent,
+    log: &Log,
+) -> Result<()> {
+    let (tx_hash, log_index) = log_identity(log)?;
+    let (block_number, _) = log_block_info(log)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO deposited_events (network, account, total_deposit, block_number, tx_hash, log_index)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (network, tx_hash, log_index) DO NOTHING
+        "#,
+        network,
+        format!("{:?}", event.account),
+        format!("0x{:064x}", event.total_deposit),
+        block_number as i64,
+        tx_hash,
+        log_index
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        error!("[{}] Failed to save Deposited event: {}", network, e);
+        e
+    })?;
+
+    Ok(())
+}
+
+fn decode_withdrawn_event(log: &Log) -> Result<WithdrawnEvent, ethers::abi::Error> {
+    if log.topics.len() != 2 {
+        warn!("Invalid number of topics for Withdrawn: expected 2, got {}", log.topics.len());
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    let data = &log.data.0;
+    if data.len() != 64 {
+        warn!("Unexpected data length for Withdrawn: expected 64, got {}", data.len());
+        return Err(ethers::abi::Error::InvalidData);
+    }
+
+    Ok(WithdrawnEvent {
+        account: log.topics[1].into(),
+        withdraw_address: Address::from_slice(&data[12..32]),
+        amount: U256::from_big_endian(&data[32..64]),
+    })
+}
+
+async fn save_withdrawn_event(
+    db_pool: &sqlx::PgPool,
+    network: &str,
+    event: WithdrawnEvent,
+    log: &Log,
+) -> Result<()> {
+    let (tx_hash, log_index) = log_identity(log)?;
+    let (block_number, _) = log_block_info(log)?;
+
+    sqlx::query!(
+        r#"
+        INSERT INTO withdrawn_events (network, account, withdraw_address, amount, block_number, tx_hash, log_index)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (network, tx_hash, log_index) DO NOTHING
+        "#,
+        network,
+        format!("{:?}", event.account),
+        format!("{:?}", event.withdraw_address),
+        format!("0x{:064x}", event.amount),
+        block_number as i64,
+        tx_hash,
+        log_index
+    )
+    .execute(db_pool)
+    .await
+    .map_err(|e| {
+        error!("[{}] Failed to save Withdrawn event: {}", network, e);
+        e
+    })?;
+
+    Ok(())
+}
+
+// --- Read-side query API -------------------------------------------------
+//
+// The indexer only writes to Postgres; everything below serves what it has
+// already written over HTTP so consumers don't have to query the database
+// directly.
+
+const DEFAULT_API_BIND_ADDR: &str = "0.0.0.0:8080";
+
+#[derive(Clone)]
+struct ApiState {
+    db_pool: sqlx::PgPool,
+    providers: Arc<HashMap<String, Arc<Provider<Ws>>>>,
+}
+
+enum ApiError {
+    NotFound,
+    Internal(eyre::Report),
+}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        ApiError::Internal(e.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        match self {
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "not found").into_response(),
+            ApiError::Internal(e) => {
+                error!("API request failed: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal error").into_response()
+            }
+        }
+    }
+}
+
+/// Binds and serves the read-side API. Runs alongside the indexing tasks for
+/// the lifetime of the process.
+async fn serve_api(state: ApiState) -> Result<()> {
+    let bind_addr = std::env::var("API_BIND_ADDR").unwrap_or_else(|_| DEFAULT_API_BIND_ADDR.to_string());
+
+    let app = Router::new()
+        .route("/user-ops/:user_op_hash", get(get_user_op))
+        .route("/user-ops", get(list_user_ops))
+        .route("/gas-stats", get(get_gas_stats))
+        .route("/sync-status", get(get_sync_status))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(&bind_addr).await
+        .map_err(|e| {
+            error!("Failed to bind API server to {}: {}", bind_addr, e);
+            e
+        })?;
+
+    info!("API server listening on {}", bind_addr);
+
+    axum::serve(listener, app).await.map_err(|e| {
+        error!("API server error: {}", e);
+        e
+    })?;
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct UserOpResponse {
+    network: String,
+    user_op_hash: String,
+    sender: String,
+    paymaster: String,
+    nonce: String,
+    success: bool,
+    actual_gas_cost: i64,
+    actual_gas_used: i64,
+    block_number: i64,
+}
+
+/// `GET /user-ops/:user_op_hash` - the event with the highest block number
+/// for a user op hash. Nonce/hash pairs are expected to be unique in practice, but
+/// we order by block number to stay well-defined if one isn't.
+async fn get_user_op(
+    State(state): State<ApiState>,
+    Path(user_op_hash): Path<String>,
+) -> Result<Json<UserOpResponse>, ApiError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT network, user_op_hash, sender, paymaster, nonce, success, actual_gas_cost, actual_gas_used, block_number
+        FROM user_operation_events
+        WHERE user_op_hash = $1
+        ORDER BY block_number DESC
+        LIMIT 1
+        "#,
+        user_op_hash
+    )
+    .fetch_optional(&state.db_pool)
+    .await?
+    .ok_or(ApiError::NotFound)?;
+
+    Ok(Json(UserOpResponse {
+        network: row.network,
+        user_op_hash: row.user_op_hash,
+        sender: row.sender,
+        paymaster: row.paymaster,
+        nonce: row.nonce,
+        success: row.success,
+        actual_gas_cost: row.actual_gas_cost,
+        actual_gas_used: row.actual_gas_used,
+        block_number: row.block_number,
+    }))
+}
+
+#[derive(Deserialize)]
+struct ListUserOpsParams {
+    network: Option<String>,
+    sender: Option<String>,
+    paymaster: Option<String>,
+    page: Option<i64>,
+    page_size: Option<i64>,
+}
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 500;
+
+/// `GET /user-ops?sender=...&paymaster=...&network=...&page=&page_size=`
+async fn list_user_ops(
+    State(state): State<ApiState>,
+    Query(params): Query<ListUserOpsParams>,
+) -> Result<Json<Vec<UserOpResponse>>, ApiError> {
+    let page_size = params.page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let page = params.page.unwrap_or(0).max(0);
+    let offset = page * page_size;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT network, user_op_hash, sender, paymaster, nonce, success, actual_gas_cost, actual_gas_used, block_number
+        FROM user_operation_events
+        WHERE ($1::text IS NULL OR network = $1)
+          AND ($2::text IS NULL OR sender = $2)
+          AND ($3::text IS NULL OR paymaster = $3)
+        ORDER BY block_number DESC
+        LIMIT $4 OFFSET $5
+        "#,
+        params.network,
+        params.sender,
+        params.paymaster,
+        page_size,
+        offset
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(
+        rows.into_iter()
+            .map(|row| UserOpResponse {
+                network: row.network,
+                user_op_hash: row.user_op_hash,
+                sender: row.sender,
+                paymaster: row.paymaster,
+                nonce: row.nonce,
+                success: row.success,
+                actual_gas_cost: row.actual_gas_cost,
+                actual_gas_used: row.actual_gas_used,
+                block_number: row.block_number,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+struct GasStatsParams {
+    network: Option<String>,
+    sender: Option<String>,
+    from_block: i64,
+    to_block: i64,
+}
+
+#[derive(Serialize)]
+struct GasStatsEntry {
+    sender: String,
+    // Wei totals can exceed i64::MAX well within normal mainnet volume, so
+    // these are aggregated and returned as `numeric` text rather than bigint.
+    total_gas_cost: String,
+    total_gas_used: String,
+    op_count: i64,
+}
+
+/// `GET /gas-stats?from_block=&to_block=&network=&sender=` - total gas cost
+/// and usage per sender over a block range.
+async fn get_gas_stats(
+    State(state): State<ApiState>,
+    Query(params): Query<GasStatsParams>,
+) -> Result<Json<Vec<GasStatsEntry>>, ApiError> {
+    let rows = sqlx::query!(
+        r#"
+        SELECT
+            sender,
+            SUM(actual_gas_cost)::text as "total_gas_cost!",
+            SUM(actual_gas_used)::text as "total_gas_used!",
+            COUNT(*) as "op_count!"
+        FROM user_operation_events
+        WHERE block_number BETWEEN $1 AND $2
+          AND ($3::text IS NULL OR network = $3)
+          AND ($4::text IS NULL OR sender = $4)
+        GROUP BY sender
+        ORDER BY SUM(actual_gas_cost) DESC
+        "#,
+        params.from_block,
+        params.to_block,
+        params.network,
+        params.sender
+    )
+    .fetch_all(&state.db_pool)
+    .await?;
+
+    Ok(Json(
+        rows.into_iter()
+            .map(|row| GasStatsEntry {
+                sender: row.sender,
+                total_gas_cost: row.total_gas_cost,
+                total_gas_used: row.total_gas_used,
+                op_count: row.op_count,
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Serialize)]
+struct SyncStatusEntry {
+    network: String,
+    checkpoint_block: Option<u64>,
+    chain_head: u64,
+    blocks_behind: u64,
+}
+
+/// `GET /sync-status` - checkpoint height vs chain head for every configured
+/// network, so callers know how far behind the indexer currently is.
+async fn get_sync_status(State(state): State<ApiState>) -> Result<Json<Vec<SyncStatusEntry>>, ApiError> {
+    let mut statuses = Vec::with_capacity(state.providers.len());
+
+    for (network, provider) in state.providers.iter() {
+        let chain_head = provider
+            .get_block_number()
+            .await
+            .map_err(|e| ApiError::Internal(e.into()))?
+            .as_u64();
+        let checkpoint_block = get_checkpoint(&state.db_pool, network).await?;
+        let blocks_behind = checkpoint_block.map_or(chain_head, |c| chain_head.saturating_sub(c));
+
+        statuses.push(SyncStatusEntry {
+            network: network.clone(),
+            checkpoint_block,
+            chain_head,
+            blocks_behind,
+        });
+    }
+
+    Ok(Json(statuses))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address_topic(address: Address) -> H256 {
+        let mut bytes = [0u8; 32];
+        bytes[12..32].copy_from_slice(address.as_bytes());
+        H256::from(bytes)
+    }
+
+    fn fixture_log(topics: Vec<H256>, data: Vec<u8>) -> Log {
+        Log {
+            topics,
+            data: Bytes::from(data),
+            ..Default::default()
+        }
+    }
+
+    // --- is_too_many_results_error ---
+
+    #[test]
+    fn is_too_many_results_error_matches_known_phrasings() {
+        let phrasings = [
+            "query returned more than 10000 results",
+            "response size exceeded",
+            "block range is too large",
+            "request timeout",
+            "too many requests",
+        ];
+
+        for message in phrasings {
+            let error = ProviderError::CustomError(message.to_string());
+            assert!(is_too_many_results_error(&error), "expected match for: {message}");
+        }
+    }
+
+    #[test]
+    fn is_too_many_results_error_ignores_unrelated_errors() {
+        let error = ProviderError::CustomError("nonce too low".to_string());
+        assert!(!is_too_many_results_error(&error));
+    }
+
+    // --- reorg_search_exhausted ---
+
+    #[test]
+    fn reorg_search_exhausted_at_genesis() {
+        assert!(reorg_search_exhausted(0, 100));
+    }
+
+    #[test]
+    fn reorg_search_exhausted_at_max_depth() {
+        assert!(reorg_search_exhausted(1000 - MAX_REORG_DEPTH, 1000));
+    }
+
+    #[test]
+    fn reorg_search_not_exhausted_mid_walk() {
+        assert!(!reorg_search_exhausted(1000 - MAX_REORG_DEPTH + 1, 1000));
+    }
+
+    // --- decode_account_deployed_event ---
+
+    #[test]
+    fn decode_account_deployed_event_valid() {
+        let user_op_hash = H256::repeat_byte(0xAA);
+        let sender = Address::repeat_byte(0x11);
+        let factory = Address::repeat_byte(0x22);
+        let paymaster = Address::repeat_byte(0x33);
+
+        let mut data = vec![0u8; 64];
+        data[12..32].copy_from_slice(factory.as_bytes());
+        data[44..64].copy_from_slice(paymaster.as_bytes());
+
+        let log = fixture_log(
+            vec![account_deployed_topic0(), user_op_hash, address_topic(sender)],
+            data,
+        );
+
+        let event = decode_account_deployed_event(&log).expect("valid fixture should decode");
+        assert_eq!(event.user_op_hash, user_op_hash);
+        assert_eq!(event.sender, sender);
+        assert_eq!(event.factory, factory);
+        assert_eq!(event.paymaster, paymaster);
+    }
+
+    #[test]
+    fn decode_account_deployed_event_rejects_wrong_topic_count() {
+        let log = fixture_log(vec![account_deployed_topic0(), H256::zero()], vec![0u8; 64]);
+        assert!(decode_account_deployed_event(&log).is_err());
+    }
+
+    #[test]
+    fn decode_account_deployed_event_rejects_malformed_data_length() {
+        let log = fixture_log(
+            vec![account_deployed_topic0(), H256::zero(), H256::zero()],
+            vec![0u8; 32],
+        );
+        assert!(decode_account_deployed_event(&log).is_err());
+    }
+
+    // --- decode_withdrawn_event ---
+
+    #[test]
+    fn decode_withdrawn_event_valid() {
+        let account = Address::repeat_byte(0x44);
+        let withdraw_address = Address::repeat_byte(0x55);
+        let amount = U256::from(12345u64);
+
+        let mut data = vec![0u8; 64];
+        data[12..32].copy_from_slice(withdraw_address.as_bytes());
+        amount.to_big_endian(&mut data[32..64]);
+
+        let log = fixture_log(vec![withdrawn_topic0(), address_topic(account)], data);
+
+        let event = decode_withdrawn_event(&log).expect("valid fixture should decode");
+        assert_eq!(event.account, account);
+        assert_eq!(event.withdraw_address, withdraw_address);
+        assert_eq!(event.amount, amount);
+    }
+
+    #[test]
+    fn decode_withdrawn_event_rejects_malformed_data_length() {
+        let log = fixture_log(vec![withdrawn_topic0(), H256::zero()], vec![0u8; 63]);
+        assert!(decode_withdrawn_event(&log).is_err());
+    }
+
+    // --- decode_user_operation_revert_reason_event ---
+
+    fn revert_reason_data(offset_word: U256, reason: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 64];
+        offset_word.to_big_endian(&mut data[32..64]);
+
+        let mut length_word = vec![0u8; 32];
+        U256::from(reason.len()).to_big_endian(&mut length_word);
+        data.extend(length_word);
+        data.extend_from_slice(reason);
+        data
+    }
+
+    #[test]
+    fn decode_user_operation_revert_reason_event_valid() {
+        let user_op_hash = H256::repeat_byte(0xBB);
+        let sender = Address::repeat_byte(0x66);
+        let reason = b"AA23 reverted";
+
+        let data = revert_reason_data(U256::from(64u64), reason);
+        let log = fixture_log(
+            vec![user_operation_revert_reason_topic0(), user_op_hash, address_topic(sender)],
+            data,
+        );
+
+        let event = decode_user_operation_revert_reason_event(&log).expect("valid fixture should decode");
+        assert_eq!(event.user_op_hash, user_op_hash);
+        assert_eq!(event.sender, sender);
+        assert_eq!(event.revert_reason.to_vec(), reason);
+    }
+
+    #[test]
+    fn decode_user_operation_revert_reason_event_rejects_malformed_length() {
+        // Claims a bytes length far larger than the data actually contains.
+        let data = revert_reason_data(U256::from(64u64), b"short");
+        let mut data = data;
+        let bogus_length = U256::from(1_000_000u64);
+        bogus_length.to_big_endian(&mut data[64..96]);
+
+        let log = fixture_log(
+            vec![user_operation_revert_reason_topic0(), H256::zero(), H256::zero()],
+            data,
+        );
+
+        assert!(decode_user_operation_revert_reason_event(&log).is_err());
+    }
+
+    #[test]
+    fn decode_user_operation_revert_reason_event_rejects_oversized_offset() {
+        // An offset word that doesn't fit in `usize` must error out rather
+        // than panic, as an `as_usize()` conversion would.
+        let data = revert_reason_data(U256::MAX, b"unreachable");
+        let log = fixture_log(
+            vec![user_operation_revert_reason_topic0(), H256::zero(), H256::zero()],
+            data,
+        );
+
+        assert!(decode_user_operation_revert_reason_event(&log).is_err());
+    }
+}